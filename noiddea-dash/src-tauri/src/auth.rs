@@ -1,6 +1,12 @@
-use bcrypt::{hash, verify, DEFAULT_COST};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use bcrypt::verify as bcrypt_verify;
+use chrono::{Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::errors::ErrorKind;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use std::sync::Arc;
+use tauri::Manager;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DatabaseResult<T = serde_json::Value> {
@@ -27,12 +33,71 @@ impl<T> DatabaseResult<T> {
     }
 }
 
+/// Claims embedded in signed auth tokens.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub email: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// Default token lifetime when no TTL is supplied by the caller.
+const DEFAULT_TOKEN_TTL_HOURS: i64 = 24;
+
+/// Holds the HS256 signing secret for the lifetime of the app.
+///
+/// The secret is resolved once at setup time from `AUTH_SECRET` (env) or,
+/// failing that, a `auth_secret` file under the app config directory so it
+/// survives restarts without being baked into the binary.
+pub struct AuthState {
+    pub secret: Arc<Vec<u8>>,
+}
+
+impl AuthState {
+    pub fn new(app: &tauri::AppHandle) -> Result<Self, String> {
+        if let Ok(secret) = std::env::var("AUTH_SECRET") {
+            return Ok(Self {
+                secret: Arc::new(secret.into_bytes()),
+            });
+        }
+
+        let app_config_dir = app
+            .path()
+            .app_config_dir()
+            .map_err(|e| format!("Could not get app config directory: {}", e))?;
+
+        std::fs::create_dir_all(&app_config_dir)
+            .map_err(|e| format!("Could not create app config directory: {}", e))?;
+
+        let secret_path = app_config_dir.join("auth_secret");
+
+        if let Ok(existing) = std::fs::read(&secret_path) {
+            return Ok(Self {
+                secret: Arc::new(existing),
+            });
+        }
+
+        let generated: Vec<u8> = (0..64).map(|_| rand::random::<u8>()).collect();
+        std::fs::write(&secret_path, &generated)
+            .map_err(|e| format!("Could not persist auth secret: {}", e))?;
+
+        Ok(Self {
+            secret: Arc::new(generated),
+        })
+    }
+}
+
 #[tauri::command]
 pub async fn auth_hash_password(
     password: String,
 ) -> Result<DatabaseResult<serde_json::Value>, String> {
-    match hash(password, DEFAULT_COST) {
-        Ok(hash) => Ok(DatabaseResult::success(serde_json::json!({ "hash": hash }))),
+    let salt = SaltString::generate(&mut argon2::password_hash::rand_core::OsRng);
+
+    match Argon2::default().hash_password(password.as_bytes(), &salt) {
+        Ok(hash) => Ok(DatabaseResult::success(
+            serde_json::json!({ "hash": hash.to_string() }),
+        )),
         Err(e) => Ok(DatabaseResult::error(format!(
             "Password hashing error: {}",
             e
@@ -40,30 +105,102 @@ pub async fn auth_hash_password(
     }
 }
 
+/// Verifies a password against either a modern Argon2id hash or a legacy
+/// bcrypt hash, identified by the stored hash's PHC-style prefix.
 #[tauri::command]
 pub async fn auth_verify_password(
     password: String,
     hash: String,
 ) -> Result<DatabaseResult<serde_json::Value>, String> {
-    match verify(password, &hash) {
-        Ok(is_valid) => Ok(DatabaseResult::success(
-            serde_json::json!({ "isValid": is_valid }),
+    if hash.starts_with("$argon2") {
+        let parsed = match PasswordHash::new(&hash) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                return Ok(DatabaseResult::error(format!(
+                    "Password verification error: {}",
+                    e
+                )))
+            }
+        };
+
+        let is_valid = Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok();
+
+        return Ok(DatabaseResult::success(serde_json::json!({
+            "isValid": is_valid,
+            "needsRehash": false
+        })));
+    }
+
+    if hash.starts_with("$2") {
+        return match bcrypt_verify(password, &hash) {
+            Ok(is_valid) => Ok(DatabaseResult::success(serde_json::json!({
+                "isValid": is_valid,
+                "needsRehash": is_valid
+            }))),
+            Err(e) => Ok(DatabaseResult::error(format!(
+                "Password verification error: {}",
+                e
+            ))),
+        };
+    }
+
+    Ok(DatabaseResult::error(
+        "Unrecognized password hash format".to_string(),
+    ))
+}
+
+#[tauri::command]
+pub async fn auth_generate_token(
+    user_id: String,
+    email: String,
+    ttl_hours: Option<i64>,
+    state: tauri::State<'_, AuthState>,
+) -> Result<DatabaseResult<serde_json::Value>, String> {
+    let now = Utc::now();
+    let ttl = ttl_hours.unwrap_or(DEFAULT_TOKEN_TTL_HOURS);
+
+    let claims = Claims {
+        sub: user_id,
+        email,
+        iat: now.timestamp(),
+        exp: (now + Duration::hours(ttl)).timestamp(),
+    };
+
+    match encode(
+        &Header::new(jsonwebtoken::Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(&state.secret),
+    ) {
+        Ok(token) => Ok(DatabaseResult::success(
+            serde_json::json!({ "token": token }),
         )),
         Err(e) => Ok(DatabaseResult::error(format!(
-            "Password verification error: {}",
+            "Token generation error: {}",
             e
         ))),
     }
 }
 
 #[tauri::command]
-pub async fn auth_generate_token(
-    _user_id: String,
-    _email: String,
-) -> Result<DatabaseResult<serde_json::Value>, String> {
-    // Generate a simple token (in production, use JWT or similar)
-    let token = format!("{}-{}", Uuid::new_v4(), chrono::Utc::now().timestamp());
-    Ok(DatabaseResult::success(
-        serde_json::json!({ "token": token }),
-    ))
+pub async fn auth_verify_token(
+    token: String,
+    state: tauri::State<'_, AuthState>,
+) -> Result<DatabaseResult<Claims>, String> {
+    match decode::<Claims>(
+        &token,
+        &DecodingKey::from_secret(&state.secret),
+        &Validation::new(jsonwebtoken::Algorithm::HS256),
+    ) {
+        Ok(data) => Ok(DatabaseResult::success(data.claims)),
+        Err(e) => {
+            let message = match e.kind() {
+                ErrorKind::ExpiredSignature => "Token has expired".to_string(),
+                ErrorKind::InvalidSignature => "Token signature is invalid".to_string(),
+                _ => format!("Token verification error: {}", e),
+            };
+            Ok(DatabaseResult::error(message))
+        }
+    }
 }
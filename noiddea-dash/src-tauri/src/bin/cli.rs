@@ -0,0 +1,161 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use noiddea_dash_lib::{auth, db};
+
+/// Command-line access to the database and auth helpers that back the
+/// noiddea-dash desktop app, for scripting, backups, and CI seeding.
+#[derive(Parser)]
+#[command(name = "noiddea-cli", version, about)]
+struct Cli {
+    /// Path to the SQLite database file. Defaults to the same database.db
+    /// the desktop app uses.
+    #[arg(long, global = true)]
+    db: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a SQL query, printing each resulting row as a JSON line
+    Query {
+        sql: String,
+        /// Bind parameter, may be repeated in positional order. Plain text
+        /// binds as a string; prefix with `int:`, `real:`, `bool:`, `blob:`
+        /// (base64) or `null` to bind another type, e.g. `--param int:42`.
+        #[arg(long = "param")]
+        params: Vec<String>,
+    },
+    /// Run a single parameterized write statement, printing the resulting
+    /// changes/lastInsertRowid as a JSON line
+    Execute {
+        sql: String,
+        /// Bind parameter, may be repeated in positional order. Same
+        /// type prefixes as `query`'s `--param`.
+        #[arg(long = "param")]
+        params: Vec<String>,
+    },
+    /// Run every statement in a SQL file as a batch
+    Exec { sql_file: PathBuf },
+    /// Hash a password with Argon2id
+    Hash { password: String },
+    /// Verify a password against a stored hash
+    Verify { password: String, hash: String },
+}
+
+/// Parses a `--param` value into the same JSON shapes `query_db`/`execute_db`
+/// accept, so the CLI isn't limited to strings. A bare value (no recognized
+/// prefix) binds as a string.
+fn parse_param(raw: &str) -> Result<serde_json::Value, String> {
+    if raw == "null" {
+        return Ok(serde_json::Value::Null);
+    }
+    if let Some(rest) = raw.strip_prefix("int:") {
+        return rest
+            .parse::<i64>()
+            .map(serde_json::Value::from)
+            .map_err(|e| format!("Invalid --param int value `{}`: {}", rest, e));
+    }
+    if let Some(rest) = raw.strip_prefix("real:") {
+        return rest
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number)
+            .ok_or_else(|| format!("Invalid --param real value `{}`", rest));
+    }
+    if let Some(rest) = raw.strip_prefix("bool:") {
+        return rest
+            .parse::<bool>()
+            .map(serde_json::Value::Bool)
+            .map_err(|e| format!("Invalid --param bool value `{}`: {}", rest, e));
+    }
+    if let Some(rest) = raw.strip_prefix("blob:") {
+        return Ok(serde_json::json!({ "$blob": rest }));
+    }
+    if let Some(rest) = raw.strip_prefix("str:") {
+        return Ok(serde_json::Value::String(rest.to_string()));
+    }
+    Ok(serde_json::Value::String(raw.to_string()))
+}
+
+/// Mirrors the app-data resolution `db_get_path` does via Tauri, but without
+/// an `AppHandle` to ask.
+fn default_db_path() -> String {
+    dirs::data_dir()
+        .map(|dir| dir.join("com.noiddea.dash").join("database.db"))
+        .unwrap_or_else(|| PathBuf::from("database.db"))
+        .to_string_lossy()
+        .to_string()
+}
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+    let cli = Cli::parse();
+    let db_path = cli.db.unwrap_or_else(default_db_path);
+
+    match cli.command {
+        Command::Query { sql, params } => {
+            let params: Vec<serde_json::Value> = params
+                .iter()
+                .map(|p| parse_param(p))
+                .collect::<Result<_, _>>()?;
+            let result = db::query_db(&db_path, &sql, &params).await?;
+            if let Some(rows) = result.data {
+                for row in rows {
+                    println!("{}", row);
+                }
+            } else if let Some(error) = result.error {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        Command::Execute { sql, params } => {
+            let params: Vec<serde_json::Value> = params
+                .iter()
+                .map(|p| parse_param(p))
+                .collect::<Result<_, _>>()?;
+            let result = db::execute_db(&db_path, &sql, &params).await?;
+            match result.data {
+                Some(data) => println!("{}", data),
+                None => {
+                    eprintln!("{}", result.error.unwrap_or_default());
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Exec { sql_file } => {
+            let sql = std::fs::read_to_string(&sql_file)
+                .map_err(|e| format!("Could not read {}: {}", sql_file.display(), e))?;
+            let result = db::exec_db(&db_path, &sql).await?;
+            if !result.success {
+                eprintln!("{}", result.error.unwrap_or_default());
+                std::process::exit(1);
+            }
+        }
+        Command::Hash { password } => {
+            let result = auth::auth_hash_password(password).await?;
+            match result.data {
+                Some(data) => println!("{}", data),
+                None => {
+                    eprintln!("{}", result.error.unwrap_or_default());
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Verify { password, hash } => {
+            let result = auth::auth_verify_password(password, hash).await?;
+            match result.data {
+                Some(data) => println!("{}", data),
+                None => {
+                    eprintln!("{}", result.error.unwrap_or_default());
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,8 +1,83 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::{Connection, Row};
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
+use std::time::Duration;
 use tauri::{Manager, State};
 
+type SqlitePool = Pool<SqliteConnectionManager>;
+
+/// Embedded, ordered schema migrations, keyed by integer version.
+///
+/// Append new entries with the next version number; never edit or reorder
+/// an entry once it has shipped, since `schema_migrations` records which
+/// versions a given database has already applied.
+///
+/// `reset-db.cjs` (the Node script this migrator replaces) isn't part of
+/// this tree, so there's nothing to port verbatim. Version 1 instead lays
+/// down a `users` table keyed by email, with a column for the password hash
+/// `auth_hash_password`/`auth_verify_password` produce and consume (the
+/// `auth` module itself does not read or write this table). Future schema changes get
+/// appended here as version 2, 3, ...
+const MIGRATIONS: &[(i64, &str)] = &[(
+    1,
+    "CREATE TABLE IF NOT EXISTS users (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        email TEXT NOT NULL UNIQUE,
+        password_hash TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT (datetime('now'))
+    )",
+)];
+
+/// Applies any migrations in `MIGRATIONS` newer than the database's current
+/// version, each in its own transaction that also records the version row.
+/// Returns the versions that were applied, in ascending order.
+fn run_migrations(conn: &mut Connection) -> Result<Vec<i64>, String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            applied_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Could not create schema_migrations table: {}", e))?;
+
+    let current_version: i64 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Could not read schema version: {}", e))?;
+
+    let mut applied = Vec::new();
+
+    for (version, sql) in MIGRATIONS.iter().filter(|(v, _)| *v > current_version) {
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Could not start migration transaction: {}", e))?;
+
+        tx.execute_batch(sql)
+            .map_err(|e| format!("Migration {} failed: {}", version, e))?;
+
+        tx.execute(
+            "INSERT INTO schema_migrations (version, applied_at) VALUES (?1, datetime('now'))",
+            [version],
+        )
+        .map_err(|e| format!("Could not record migration {}: {}", version, e))?;
+
+        tx.commit()
+            .map_err(|e| format!("Could not commit migration {}: {}", version, e))?;
+
+        applied.push(*version);
+    }
+
+    Ok(applied)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DatabaseResult<T = serde_json::Value> {
     pub success: bool,
@@ -19,7 +94,6 @@ impl<T> DatabaseResult<T> {
         }
     }
 
-    #[allow(dead_code)]
     pub fn error(error: String) -> Self {
         Self {
             success: false,
@@ -29,24 +103,31 @@ impl<T> DatabaseResult<T> {
     }
 }
 
+/// Default number of pooled connections when `DB_POOL_SIZE` isn't set.
+const DEFAULT_POOL_SIZE: u32 = 8;
+
 pub struct DatabaseState {
-    pub conn: Arc<Mutex<Option<Connection>>>,
+    pool: Mutex<Option<SqlitePool>>,
+    pool_size: u32,
 }
 
 impl DatabaseState {
     pub fn new() -> Self {
+        let pool_size = std::env::var("DB_POOL_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_POOL_SIZE);
+
         Self {
-            conn: Arc::new(Mutex::new(None)),
+            pool: Mutex::new(None),
+            pool_size,
         }
     }
 
-    pub fn get_connection(
-        &self,
-        app: &tauri::AppHandle,
-    ) -> Result<Arc<Mutex<Option<Connection>>>, String> {
-        let mut conn_opt = self.conn.lock().unwrap();
+    pub fn get_pool(&self, app: &tauri::AppHandle) -> Result<SqlitePool, String> {
+        let mut pool_opt = self.pool.lock().unwrap();
 
-        if conn_opt.is_none() {
+        if pool_opt.is_none() {
             let app_data_dir = app
                 .path()
                 .app_data_dir()
@@ -56,65 +137,217 @@ impl DatabaseState {
                 .map_err(|e| format!("Could not create app data directory: {}", e))?;
 
             let db_path = app_data_dir.join("database.db");
-            let conn = Connection::open(&db_path)
-                .map_err(|e| format!("Could not open database: {}", e))?;
-
-            // Enable WAL mode for better concurrency
-            // PRAGMA journal_mode returns a value, so we need to use query_row
-            let journal_mode: String = conn
-                .query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))
-                .map_err(|e| format!("Could not set WAL mode: {}", e))?;
-
-            // Verify WAL mode was set (should return "wal")
-            if journal_mode.to_lowercase() != "wal" {
-                return Err(format!("Failed to set WAL mode, got: {}", journal_mode));
-            }
 
-            // Enable foreign keys
-            // PRAGMA foreign_keys doesn't return a value, so execute is fine
-            conn.execute("PRAGMA foreign_keys = ON", [])
-                .map_err(|e| format!("Could not enable foreign keys: {}", e))?;
+            let manager = SqliteConnectionManager::file(&db_path).with_init(apply_pragmas);
 
-            // Optimización puntual para Windows: aumentar cache size
-            // Esto mejora significativamente el rendimiento en Windows sin riesgos
-            conn.execute("PRAGMA cache_size = -8192", [])
-                .unwrap_or_default(); // Ignorar errores, no crítico
+            let pool = Pool::builder()
+                .max_size(self.pool_size)
+                .connection_timeout(Duration::from_secs(5))
+                .build(manager)
+                .map_err(|e| format!("Could not build connection pool: {}", e))?;
 
-            *conn_opt = Some(conn);
+            let mut setup_conn = pool
+                .get()
+                .map_err(|e| format!("Could not acquire connection for migrations: {}", e))?;
+            run_migrations(&mut setup_conn)?;
+
+            *pool_opt = Some(pool);
         }
 
-        // Return a clone of the Arc
-        Ok(Arc::clone(&self.conn))
+        // Cloning an r2d2::Pool just clones the inner Arc, so callers get
+        // their own handle into the shared pool.
+        Ok(pool_opt.as_ref().unwrap().clone())
+    }
+
+    fn checkout(&self, app: &tauri::AppHandle) -> Result<r2d2::PooledConnection<SqliteConnectionManager>, String> {
+        self.get_pool(app)?
+            .get()
+            .map_err(|e| format!("Could not acquire pooled connection: {}", e))
     }
 }
 
-// Helper to convert JSON value to rusqlite params
-fn json_to_params(params: &[serde_json::Value]) -> Vec<Box<dyn rusqlite::ToSql + Send + Sync>> {
-    params
-        .iter()
-        .map(|v| match v {
-            serde_json::Value::Null => {
-                Box::new(None::<String>) as Box<dyn rusqlite::ToSql + Send + Sync>
-            }
-            serde_json::Value::Bool(b) => Box::new(*b) as Box<dyn rusqlite::ToSql + Send + Sync>,
-            serde_json::Value::Number(n) => {
-                if n.is_i64() {
-                    Box::new(n.as_i64().unwrap()) as Box<dyn rusqlite::ToSql + Send + Sync>
-                } else if n.is_u64() {
-                    Box::new(n.as_u64().unwrap() as i64) as Box<dyn rusqlite::ToSql + Send + Sync>
-                } else {
-                    Box::new(n.as_f64().unwrap()) as Box<dyn rusqlite::ToSql + Send + Sync>
-                }
-            }
-            serde_json::Value::String(s) => {
-                Box::new(s.clone()) as Box<dyn rusqlite::ToSql + Send + Sync>
-            }
-            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-                Box::new(serde_json::to_string(v).unwrap())
-                    as Box<dyn rusqlite::ToSql + Send + Sync>
+// Applies the pragmas every connection should start with, whether it came
+// from the pool or was opened directly (e.g. by the CLI).
+fn apply_pragmas(conn: &mut Connection) -> rusqlite::Result<()> {
+    // Enable WAL mode for better concurrency
+    // PRAGMA journal_mode returns a value, so we need to use query_row
+    let journal_mode: String =
+        conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))?;
+
+    if journal_mode.to_lowercase() != "wal" {
+        return Err(rusqlite::Error::InvalidParameterName(format!(
+            "Failed to set WAL mode, got: {}",
+            journal_mode
+        )));
+    }
+
+    // Enable foreign keys
+    // PRAGMA foreign_keys doesn't return a value, so execute is fine
+    conn.execute("PRAGMA foreign_keys = ON", [])?;
+
+    // Optimización puntual para Windows: aumentar cache size
+    // Esto mejora significativamente el rendimiento en Windows sin riesgos
+    conn.execute("PRAGMA cache_size = -8192", [])
+        .unwrap_or_default(); // Ignorar errores, no crítico
+
+    Ok(())
+}
+
+/// Opens a standalone connection to the database at `db_path` with the same
+/// pragmas the pooled connections use, creating the parent directory and
+/// applying any pending migrations first. Intended for one-off callers that
+/// don't hold a `DatabaseState`, such as the CLI binary — `DatabaseState`
+/// does the same bootstrapping in `get_pool`, but the CLI never goes
+/// through a pool, so it has to happen here instead.
+fn open_connection(db_path: &str) -> Result<Connection, String> {
+    if let Some(parent) = std::path::Path::new(db_path).parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Could not create database directory: {}", e))?;
+    }
+
+    let mut conn =
+        Connection::open(db_path).map_err(|e| format!("Could not open database: {}", e))?;
+    apply_pragmas(&mut conn).map_err(|e| format!("Could not configure database: {}", e))?;
+    run_migrations(&mut conn)?;
+    Ok(conn)
+}
+
+/// Runs a read query against an already-open connection, whether it came
+/// from the pool (Tauri commands) or was opened directly (the CLI).
+fn query_with_conn(
+    conn: &Connection,
+    sql: &str,
+    params: &[serde_json::Value],
+) -> Result<DatabaseResult<Vec<serde_json::Value>>, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("SQL prepare error: {}", e))?;
+
+    let column_count = stmt.column_count();
+    let column_names: Vec<String> = (0..column_count)
+        .map(|i| stmt.column_name(i).unwrap_or("").to_string())
+        .collect();
+
+    let param_vec = json_to_params(params)?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params_from_iter(param_vec.iter().map(|p| p.as_ref())),
+            |row| row_to_json(row, &column_names),
+        )
+        .map_err(|e| format!("SQL query error: {}", e))?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(row.map_err(|e| format!("Row parsing error: {}", e))?);
+    }
+
+    Ok(DatabaseResult::success(results))
+}
+
+/// Runs a write statement against an already-open connection, whether it
+/// came from the pool (Tauri commands) or was opened directly (the CLI).
+fn execute_with_conn(
+    conn: &Connection,
+    sql: &str,
+    params: &[serde_json::Value],
+) -> Result<DatabaseResult<serde_json::Value>, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("SQL prepare error: {}", e))?;
+
+    let param_vec = json_to_params(params)?;
+    let changes = stmt
+        .execute(rusqlite::params_from_iter(
+            param_vec.iter().map(|p| p.as_ref()),
+        ))
+        .map_err(|e| format!("SQL execute error: {}", e))?;
+
+    let last_insert_rowid = conn.last_insert_rowid();
+
+    Ok(DatabaseResult::success(serde_json::json!({
+        "changes": changes,
+        "lastInsertRowid": last_insert_rowid
+    })))
+}
+
+/// Runs a SQL batch against an already-open connection, whether it came
+/// from the pool (Tauri commands) or was opened directly (the CLI).
+fn exec_with_conn(conn: &Connection, sql: &str) -> Result<DatabaseResult<()>, String> {
+    conn.execute_batch(sql)
+        .map_err(|e| format!("SQL exec error: {}", e))?;
+    Ok(DatabaseResult::success(()))
+}
+
+/// Plain, Tauri-free implementation of `db_query`, keyed by a DB path
+/// instead of a `State<DatabaseState>`. Used by the CLI's `query`
+/// subcommand, which has no `AppHandle`/pool to draw a connection from.
+pub async fn query_db(
+    db_path: &str,
+    sql: &str,
+    params: &[serde_json::Value],
+) -> Result<DatabaseResult<Vec<serde_json::Value>>, String> {
+    let conn = open_connection(db_path)?;
+    query_with_conn(&conn, sql, params)
+}
+
+/// Plain, Tauri-free implementation of `db_execute`, keyed by a DB path
+/// instead of a `State<DatabaseState>`. Used by the CLI.
+pub async fn execute_db(
+    db_path: &str,
+    sql: &str,
+    params: &[serde_json::Value],
+) -> Result<DatabaseResult<serde_json::Value>, String> {
+    let conn = open_connection(db_path)?;
+    execute_with_conn(&conn, sql, params)
+}
+
+/// Plain, Tauri-free implementation of `db_exec`, keyed by a DB path instead
+/// of a `State<DatabaseState>`. Used by the CLI's `exec` subcommand.
+pub async fn exec_db(db_path: &str, sql: &str) -> Result<DatabaseResult<()>, String> {
+    let conn = open_connection(db_path)?;
+    exec_with_conn(&conn, sql)
+}
+
+// Helper to convert a single JSON value to a rusqlite param
+fn json_to_param(v: &serde_json::Value) -> Result<Box<dyn rusqlite::ToSql + Send + Sync>, String> {
+    Ok(match v {
+        serde_json::Value::Null => {
+            Box::new(None::<String>) as Box<dyn rusqlite::ToSql + Send + Sync>
+        }
+        serde_json::Value::Bool(b) => Box::new(*b) as Box<dyn rusqlite::ToSql + Send + Sync>,
+        serde_json::Value::Number(n) => {
+            if n.is_i64() {
+                Box::new(n.as_i64().unwrap()) as Box<dyn rusqlite::ToSql + Send + Sync>
+            } else if n.is_u64() {
+                Box::new(n.as_u64().unwrap() as i64) as Box<dyn rusqlite::ToSql + Send + Sync>
+            } else {
+                Box::new(n.as_f64().unwrap()) as Box<dyn rusqlite::ToSql + Send + Sync>
             }
-        })
-        .collect()
+        }
+        serde_json::Value::String(s) => {
+            Box::new(s.clone()) as Box<dyn rusqlite::ToSql + Send + Sync>
+        }
+        serde_json::Value::Object(obj) if obj.len() == 1 && obj.contains_key("$blob") => {
+            let encoded = obj
+                .get("$blob")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "`$blob` value must be a base64 string".to_string())?;
+            let bytes = BASE64
+                .decode(encoded)
+                .map_err(|e| format!("Invalid base64 in `$blob` param: {}", e))?;
+            Box::new(bytes) as Box<dyn rusqlite::ToSql + Send + Sync>
+        }
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            Box::new(serde_json::to_string(v).unwrap()) as Box<dyn rusqlite::ToSql + Send + Sync>
+        }
+    })
+}
+
+// Helper to convert JSON values to rusqlite params
+fn json_to_params(
+    params: &[serde_json::Value],
+) -> Result<Vec<Box<dyn rusqlite::ToSql + Send + Sync>>, String> {
+    params.iter().map(json_to_param).collect()
 }
 
 // Helper to convert a row to JSON
@@ -130,7 +363,7 @@ fn row_to_json(row: &Row, column_names: &[String]) -> Result<serde_json::Value,
             }
             rusqlite::types::Value::Text(s) => serde_json::Value::String(s),
             rusqlite::types::Value::Blob(b) => {
-                serde_json::Value::String(format!("[BLOB:{} bytes]", b.len()))
+                serde_json::json!({ "$blob": BASE64.encode(&b) })
             }
         };
         map.insert(name.clone(), json_value);
@@ -138,6 +371,53 @@ fn row_to_json(row: &Row, column_names: &[String]) -> Result<serde_json::Value,
     Ok(serde_json::Value::Object(map))
 }
 
+// Runs a single statement within a transaction, pushing its result (rows for
+// a SELECT, null otherwise) onto `results`. Used by `db_transaction` so the
+// savepoint bookkeeping around each statement stays in one place.
+fn run_query(
+    tx: &rusqlite::Transaction,
+    sql: &str,
+    params: &[serde_json::Value],
+    results: &mut Vec<serde_json::Value>,
+) -> Result<(), String> {
+    let mut stmt = tx
+        .prepare(sql)
+        .map_err(|e| format!("SQL prepare error: {}", e))?;
+
+    let param_vec = json_to_params(params)?;
+
+    // Check if it's a SELECT query
+    let sql_upper = sql.trim_start().to_uppercase();
+    if sql_upper.starts_with("SELECT") {
+        // Get column names from the statement
+        let column_count = stmt.column_count();
+        let column_names: Vec<String> = (0..column_count)
+            .map(|i| stmt.column_name(i).unwrap_or("").to_string())
+            .collect();
+
+        let rows = stmt
+            .query_map(
+                rusqlite::params_from_iter(param_vec.iter().map(|p| p.as_ref())),
+                |row| row_to_json(row, &column_names),
+            )
+            .map_err(|e| format!("SQL query error: {}", e))?;
+
+        let mut query_results = Vec::new();
+        for row in rows {
+            query_results.push(row.map_err(|e| format!("Row parsing error: {}", e))?);
+        }
+        results.push(serde_json::Value::Array(query_results));
+    } else {
+        stmt.execute(rusqlite::params_from_iter(
+            param_vec.iter().map(|p| p.as_ref()),
+        ))
+        .map_err(|e| format!("SQL execute error: {}", e))?;
+        results.push(serde_json::Value::Null);
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn db_get_path(app: tauri::AppHandle) -> Result<String, String> {
     let app_data_dir = app
@@ -164,36 +444,11 @@ pub async fn db_query(
     app: tauri::AppHandle,
     state: State<'_, DatabaseState>,
 ) -> Result<DatabaseResult<Vec<serde_json::Value>>, String> {
-    let conn_arc = state.get_connection(&app)?;
-    let conn = conn_arc.lock().unwrap();
-    let conn = conn
-        .as_ref()
-        .ok_or_else(|| "Database connection not initialized".to_string())?;
-
-    let mut stmt = conn
-        .prepare(&sql)
-        .map_err(|e| format!("SQL prepare error: {}", e))?;
-
-    // Get column names from the statement
-    let column_count = stmt.column_count();
-    let column_names: Vec<String> = (0..column_count)
-        .map(|i| stmt.column_name(i).unwrap_or("").to_string())
-        .collect();
-
-    let param_vec = json_to_params(&params);
-    let rows = stmt
-        .query_map(
-            rusqlite::params_from_iter(param_vec.iter().map(|p| p.as_ref())),
-            |row| row_to_json(row, &column_names),
-        )
-        .map_err(|e| format!("SQL query error: {}", e))?;
-
-    let mut results = Vec::new();
-    for row in rows {
-        results.push(row.map_err(|e| format!("Row parsing error: {}", e))?);
-    }
-
-    Ok(DatabaseResult::success(results))
+    let conn = match state.checkout(&app) {
+        Ok(conn) => conn,
+        Err(e) => return Ok(DatabaseResult::error(e)),
+    };
+    query_with_conn(&conn, &sql, &params)
 }
 
 #[tauri::command]
@@ -203,32 +458,11 @@ pub async fn db_execute(
     app: tauri::AppHandle,
     state: State<'_, DatabaseState>,
 ) -> Result<DatabaseResult<serde_json::Value>, String> {
-    let conn_arc = state.get_connection(&app)?;
-    let conn = conn_arc.lock().unwrap();
-    let conn = conn
-        .as_ref()
-        .ok_or_else(|| "Database connection not initialized".to_string())?;
-
-    let mut stmt = conn
-        .prepare(&sql)
-        .map_err(|e| format!("SQL prepare error: {}", e))?;
-
-    let param_vec = json_to_params(&params);
-    let result = stmt
-        .execute(rusqlite::params_from_iter(
-            param_vec.iter().map(|p| p.as_ref()),
-        ))
-        .map_err(|e| format!("SQL execute error: {}", e))?;
-
-    let changes = result;
-    let last_insert_rowid = conn.last_insert_rowid();
-
-    let result_data = serde_json::json!({
-        "changes": changes,
-        "lastInsertRowid": last_insert_rowid
-    });
-
-    Ok(DatabaseResult::success(result_data))
+    let conn = match state.checkout(&app) {
+        Ok(conn) => conn,
+        Err(e) => return Ok(DatabaseResult::error(e)),
+    };
+    execute_with_conn(&conn, &sql, &params)
 }
 
 #[tauri::command]
@@ -237,15 +471,46 @@ pub async fn db_exec(
     app: tauri::AppHandle,
     state: State<'_, DatabaseState>,
 ) -> Result<DatabaseResult<()>, String> {
-    let conn_arc = state.get_connection(&app)?;
-    let conn = conn_arc.lock().unwrap();
-    let conn = conn
-        .as_ref()
-        .ok_or_else(|| "Database connection not initialized".to_string())?;
+    let conn = match state.checkout(&app) {
+        Ok(conn) => conn,
+        Err(e) => return Ok(DatabaseResult::error(e)),
+    };
+    exec_with_conn(&conn, &sql)
+}
 
-    conn.execute_batch(&sql)
-        .map_err(|e| format!("SQL exec error: {}", e))?;
-    Ok(DatabaseResult::success(()))
+#[tauri::command]
+pub async fn db_migrate(
+    app: tauri::AppHandle,
+    state: State<'_, DatabaseState>,
+) -> Result<DatabaseResult<Vec<i64>>, String> {
+    let mut conn = match state.checkout(&app) {
+        Ok(conn) => conn,
+        Err(e) => return Ok(DatabaseResult::error(e)),
+    };
+    match run_migrations(&mut conn) {
+        Ok(applied) => Ok(DatabaseResult::success(applied)),
+        Err(e) => Ok(DatabaseResult::error(e)),
+    }
+}
+
+#[tauri::command]
+pub async fn db_schema_version(
+    app: tauri::AppHandle,
+    state: State<'_, DatabaseState>,
+) -> Result<DatabaseResult<i64>, String> {
+    let conn = match state.checkout(&app) {
+        Ok(conn) => conn,
+        Err(e) => return Ok(DatabaseResult::error(e)),
+    };
+    let version: i64 = match conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    ) {
+        Ok(version) => version,
+        Err(e) => return Ok(DatabaseResult::error(format!("Could not read schema version: {}", e))),
+    };
+    Ok(DatabaseResult::success(version))
 }
 
 #[tauri::command]
@@ -254,11 +519,10 @@ pub async fn db_transaction(
     app: tauri::AppHandle,
     state: State<'_, DatabaseState>,
 ) -> Result<DatabaseResult<Vec<serde_json::Value>>, String> {
-    let conn_arc = state.get_connection(&app)?;
-    let mut conn_guard = conn_arc.lock().unwrap();
-    let conn = conn_guard
-        .as_mut()
-        .ok_or_else(|| "Database connection not initialized".to_string())?;
+    let mut conn = match state.checkout(&app) {
+        Ok(conn) => conn,
+        Err(e) => return Ok(DatabaseResult::error(e)),
+    };
 
     let tx = conn
         .transaction()
@@ -279,39 +543,41 @@ pub async fn db_transaction(
             .cloned()
             .unwrap_or_default();
 
-        let mut stmt = tx
-            .prepare(&sql)
-            .map_err(|e| format!("SQL prepare error: {}", e))?;
-
-        let param_vec = json_to_params(&params);
-
-        // Check if it's a SELECT query
-        let sql_upper = sql.trim_start().to_uppercase();
-        if sql_upper.starts_with("SELECT") {
-            // Get column names from the statement
-            let column_count = stmt.column_count();
-            let column_names: Vec<String> = (0..column_count)
-                .map(|i| stmt.column_name(i).unwrap_or("").to_string())
-                .collect();
-
-            let rows = stmt
-                .query_map(
-                    rusqlite::params_from_iter(param_vec.iter().map(|p| p.as_ref())),
-                    |row| row_to_json(row, &column_names),
-                )
-                .map_err(|e| format!("SQL query error: {}", e))?;
-
-            let mut query_results = Vec::new();
-            for row in rows {
-                query_results.push(row.map_err(|e| format!("Row parsing error: {}", e))?);
+        let savepoint = query_obj
+            .get("savepoint")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        let rollback_on_error = query_obj
+            .get("rollbackOnError")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if let Some(name) = &savepoint {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("Invalid savepoint name: {}", name));
+            }
+
+            tx.execute_batch(&format!("SAVEPOINT {}", name))
+                .map_err(|e| format!("Could not create savepoint {}: {}", name, e))?;
+        }
+
+        let outcome = run_query(&tx, &sql, &params, &mut results);
+
+        match (outcome, &savepoint) {
+            (Ok(()), Some(name)) => {
+                tx.execute_batch(&format!("RELEASE {}", name))
+                    .map_err(|e| format!("Could not release savepoint {}: {}", name, e))?;
+            }
+            (Ok(()), None) => {}
+            (Err(e), Some(name)) if rollback_on_error => {
+                tx.execute_batch(&format!("ROLLBACK TO {}", name))
+                    .map_err(|e2| format!("Could not roll back to savepoint {}: {}", name, e2))?;
+                tx.execute_batch(&format!("RELEASE {}", name))
+                    .map_err(|e2| format!("Could not release savepoint {}: {}", name, e2))?;
+                results.push(serde_json::json!({ "$error": e }));
             }
-            results.push(serde_json::Value::Array(query_results));
-        } else {
-            stmt.execute(rusqlite::params_from_iter(
-                param_vec.iter().map(|p| p.as_ref()),
-            ))
-            .map_err(|e| format!("SQL execute error: {}", e))?;
-            results.push(serde_json::Value::Null);
+            (Err(e), _) => return Err(e),
         }
     }
 
@@ -1,5 +1,5 @@
-mod auth;
-mod db;
+pub mod auth;
+pub mod db;
 #[derive(Clone, serde::Serialize)]
 struct Payload {
     args: Vec<String>,
@@ -7,8 +7,8 @@ struct Payload {
 }
 
 use tauri::{Manager, Emitter};
-use db::{DatabaseState, db_get_path, db_exists, db_query, db_execute, db_exec, db_transaction};
-use auth::{auth_hash_password, auth_verify_password, auth_generate_token};
+use db::{DatabaseState, db_get_path, db_exists, db_query, db_execute, db_exec, db_transaction, db_migrate, db_schema_version};
+use auth::{AuthState, auth_hash_password, auth_verify_password, auth_generate_token, auth_verify_token};
 
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -22,6 +22,10 @@ pub fn run() {
             // Initialize database state
             app.manage(DatabaseState::new());
 
+            // Initialize auth state (loads/generates the JWT signing secret)
+            let auth_state = AuthState::new(app.handle())?;
+            app.manage(auth_state);
+
             // Path functionality is built into Tauri v2, no plugin needed
 
             if cfg!(debug_assertions) {
@@ -41,10 +45,13 @@ pub fn run() {
             db_execute,
             db_exec,
             db_transaction,
+            db_migrate,
+            db_schema_version,
             // Auth commands
             auth_hash_password,
             auth_verify_password,
             auth_generate_token,
+            auth_verify_token,
             // App commands
             app_get_version,
             app_get_path,
@@ -56,8 +63,6 @@ pub fn run() {
             window_maximize,
             window_close,
             window_is_maximized,
-            // Script commands
-            script_reset_database,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -141,75 +146,3 @@ async fn window_close(window: tauri::Window) -> Result<(), String> {
 async fn window_is_maximized(window: tauri::Window) -> Result<bool, String> {
     window.is_maximized().map_err(|e| e.to_string())
 }
-
-#[tauri::command]
-async fn script_reset_database(app: tauri::AppHandle) -> Result<String, String> {
-    use std::path::PathBuf;
-    use std::process::Command;
-
-    // Get the app's executable directory and navigate to project root
-    let exe_path =
-        std::env::current_exe().map_err(|e| format!("Could not get executable path: {}", e))?;
-
-    // In development, the executable is in src-tauri/target/debug or src-tauri/target/release
-    // We need to go up to the project root
-    let project_root = if cfg!(debug_assertions) {
-        // Development: go from src-tauri/target/debug/app to project root
-        // Need to go up 4 levels: app -> debug/release -> target -> src-tauri -> project root
-        exe_path
-            .parent()
-            .and_then(|p| p.parent()) // debug or release
-            .and_then(|p| p.parent()) // target
-            .and_then(|p| p.parent()) // src-tauri
-            .map(|p| p.to_path_buf())
-            .unwrap_or_else(|| PathBuf::from("."))
-    } else {
-        // Production: try to get resource dir or use executable dir
-        app.path()
-            .resource_dir()
-            .map(|p| p.parent().unwrap_or(&p).to_path_buf())
-            .unwrap_or_else(|_| {
-                exe_path
-                    .parent()
-                    .map(|p| p.to_path_buf())
-                    .unwrap_or_else(|| PathBuf::from("."))
-            })
-    };
-
-    let script_path = project_root
-        .join("src")
-        .join("scripts")
-        .join("reset-db.cjs");
-
-    // Verify script exists
-    if !script_path.exists() {
-        return Err(format!("Script not found at: {}", script_path.display()));
-    }
-
-    // Try to find node in PATH
-    let node_command = if cfg!(target_os = "windows") {
-        "node.exe"
-    } else {
-        "node"
-    };
-
-    // Execute the script
-    let output = Command::new(node_command)
-        .arg(script_path.to_string_lossy().as_ref())
-        .current_dir(&project_root)
-        .output()
-        .map_err(|e| {
-            format!(
-                "Failed to execute script: {}. Make sure Node.js is installed and in PATH.",
-                e
-            )
-        })?;
-
-    if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        Ok(stdout.to_string())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("Script execution failed: {}", stderr))
-    }
-}